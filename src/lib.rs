@@ -12,6 +12,14 @@
 //! * Obtaining the "server hash" required for authentication, available using
 //! [`server_hash`](fn.server_hash.html). Since Mojang uses abnormal hash digests
 //! for obtaining the value, this crate provides a simple way to obtain it.
+//! * Authenticating with a Microsoft account, the replacement for the legacy
+//! Yggdrasil [`client_login`](fn.client_login.html). See the
+//! [`microsoft`](microsoft/index.html) module.
+//!
+//! Every function in this crate that makes a request is a thin wrapper
+//! around a shared, lazily-initialized default [`MojangClient`]. Construct
+//! your own `MojangClient` instead if you want different endpoints or a
+//! different request timeout, e.g. to point at a mojang-compatible proxy.
 //!
 //! # Examples
 //! Authenticating a client on a server:
@@ -42,7 +50,10 @@
 
 use log::trace;
 use num_bigint::BigInt;
-use reqwest::Client;
+use once_cell::sync::Lazy;
+use reqwest::{Client, StatusCode};
+use rsa::pkcs8::DecodePublicKey;
+use rsa::{Hash, PaddingScheme, PublicKey, RsaPublicKey};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use sha1::Sha1;
@@ -50,8 +61,11 @@ use std::fmt;
 use std::fmt::{Display, Formatter};
 use std::io;
 use std::string::FromUtf8Error;
+use std::time::Duration;
 use uuid::Uuid;
 
+pub mod microsoft;
+
 type StdResult<T, E> = std::result::Result<T, E>;
 
 /// Result type used by this crate. This is equivalent
@@ -68,9 +82,30 @@ pub enum Error {
     /// Indicates that the UTF8 bytes failed to parse.
     Utf8(FromUtf8Error),
     /// Indicates that the response included malformed JSON.
-    /// This could also indicate that, for example, authentication
-    /// failed, because the response would have unexpected fields.
     Json(serde_json::Error),
+    /// Indicates that no player exists with the given username.
+    NoSuchPlayer,
+    /// Indicates that a base64-encoded profile property failed to decode.
+    Base64(base64::DecodeError),
+    /// Indicates that the session server rejected a `hasJoined` check,
+    /// meaning the client did not authenticate correctly.
+    AuthenticationFailed,
+    /// Indicates that the Mojang API returned an error response, such as a
+    /// wrong password, a forbidden operation, or a rate limit.
+    Api {
+        /// The HTTP status code of the response.
+        status: u16,
+        /// The short, machine-readable error identifier, e.g.
+        /// `"ForbiddenOperationException"`.
+        error: String,
+        /// A human-readable description of the error.
+        error_message: String,
+    },
+    /// Indicates that a response was valid JSON (or otherwise well-formed),
+    /// but did not have the structure this crate expected, e.g. a profile
+    /// property missing an expected texture, or an Xbox Live response with
+    /// no `xui` claims.
+    InvalidResponse(String),
 }
 
 impl Display for Error {
@@ -80,6 +115,15 @@ impl Display for Error {
             Error::Http(e) => write!(f, "{}", e)?,
             Error::Utf8(e) => write!(f, "{}", e)?,
             Error::Json(e) => write!(f, "{}", e)?,
+            Error::NoSuchPlayer => write!(f, "no such player")?,
+            Error::Base64(e) => write!(f, "{}", e)?,
+            Error::AuthenticationFailed => write!(f, "authentication failed")?,
+            Error::Api {
+                status,
+                error,
+                error_message,
+            } => write!(f, "{} (HTTP {}): {}", error, status, error_message)?,
+            Error::InvalidResponse(message) => write!(f, "invalid response: {}", message)?,
         }
         Ok(())
     }
@@ -92,6 +136,22 @@ impl PartialEq for Error {
             (Error::Http(e1), Error::Http(e2)) => e1.to_string() == e2.to_string(),
             (Error::Utf8(e1), Error::Utf8(e2)) => e1.to_string() == e2.to_string(),
             (Error::Json(e1), Error::Json(e2)) => e1.to_string() == e2.to_string(),
+            (Error::NoSuchPlayer, Error::NoSuchPlayer) => true,
+            (Error::Base64(e1), Error::Base64(e2)) => e1.to_string() == e2.to_string(),
+            (Error::AuthenticationFailed, Error::AuthenticationFailed) => true,
+            (
+                Error::Api {
+                    status: s1,
+                    error: e1,
+                    error_message: m1,
+                },
+                Error::Api {
+                    status: s2,
+                    error: e2,
+                    error_message: m2,
+                },
+            ) => s1 == s2 && e1 == e2 && m1 == m2,
+            (Error::InvalidResponse(m1), Error::InvalidResponse(m2)) => m1 == m2,
             _ => false,
         }
     }
@@ -134,20 +194,554 @@ pub struct ProfileProperty {
     pub signature: String,
 }
 
-/// Performs server-side authentication using the given server hash
-/// and username.
+impl ProfileProperty {
+    /// Decodes a `textures` profile property into a [`Textures`] struct
+    /// containing the player's skin and cape URLs.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use mojang_api::ProfileProperty;
+    /// # fn textures_property() -> ProfileProperty { unimplemented!() }
+    /// let property = textures_property();
+    /// let textures = property.decode_textures()?;
+    /// println!("Skin URL: {}", textures.skin_url);
+    /// # Ok::<(), mojang_api::Error>(())
+    /// ```
+    pub fn decode_textures(&self) -> Result<Textures> {
+        let decoded = base64::decode(&self.value).map_err(Error::Base64)?;
+        let json = String::from_utf8(decoded).map_err(Error::Utf8)?;
+        let payload: TexturesPayload = serde_json::from_str(&json).map_err(Error::Json)?;
+
+        let skin = payload
+            .textures
+            .skin
+            .ok_or_else(|| Error::InvalidResponse("missing SKIN texture".to_string()))?;
+
+        let skin_model = skin
+            .metadata
+            .and_then(|metadata| metadata.model)
+            .map(|model| match model.as_str() {
+                "slim" => SkinModel::Slim,
+                _ => SkinModel::Classic,
+            });
+
+        Ok(Textures {
+            skin_url: skin.url,
+            skin_model,
+            cape_url: payload.textures.cape.map(|cape| cape.url),
+        })
+    }
+
+    /// Verifies this property's `signature` against Yggdrasil's RSA public
+    /// key, so that a profile property forwarded by a client can be trusted
+    /// without contacting Mojang again.
+    ///
+    /// `public_key_der` is Yggdrasil's public key, DER-encoded as a
+    /// `SubjectPublicKeyInfo` (the key published at
+    /// <https://wiki.vg/Microsoft_Authentication_Scheme#Signature_Verification>
+    /// is the PEM-encoded form of it).
+    ///
+    /// Returns `false` (rather than an error) if the public key is invalid
+    /// or the signature does not match, since either case simply means the
+    /// property cannot be trusted.
+    pub fn verify_signature(&self, public_key_der: &[u8]) -> bool {
+        let public_key = match RsaPublicKey::from_public_key_der(public_key_der) {
+            Ok(public_key) => public_key,
+            Err(_) => return false,
+        };
+
+        let signature = match base64::decode(&self.signature) {
+            Ok(signature) => signature,
+            Err(_) => return false,
+        };
+
+        let mut hasher = Sha1::new();
+        hasher.update(self.value.as_bytes());
+        let digest = hasher.digest().bytes();
+
+        let padding = PaddingScheme::new_pkcs1v15_sign(Some(Hash::SHA1));
+        public_key.verify(padding, &digest, &signature).is_ok()
+    }
+}
+
+/// The decoded form of a `textures` profile property, containing URLs to
+/// the player's skin and (optionally) cape.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Textures {
+    /// The URL of the player's skin texture.
+    pub skin_url: String,
+    /// The skin model variant, if one was specified. Absent when the player
+    /// uses the default classic model.
+    pub skin_model: Option<SkinModel>,
+    /// The URL of the player's cape texture, if they have one equipped.
+    pub cape_url: Option<String>,
+}
+
+/// The model variant of a player's skin texture.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SkinModel {
+    /// The default, wide-armed ("Steve") model.
+    Classic,
+    /// The slim-armed ("Alex") model.
+    Slim,
+}
+
+/// Raw shape of the JSON contained in a decoded `textures` property value.
+#[derive(Debug, Clone, Deserialize)]
+struct TexturesPayload {
+    textures: TextureUrls,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct TextureUrls {
+    #[serde(rename = "SKIN")]
+    skin: Option<SkinTexture>,
+    #[serde(rename = "CAPE")]
+    cape: Option<CapeTexture>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct SkinTexture {
+    url: String,
+    #[serde(default)]
+    metadata: Option<SkinMetadata>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct SkinMetadata {
+    #[serde(default)]
+    model: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct CapeTexture {
+    url: String,
+}
+
+/// The shape of an error response returned by most Mojang API endpoints.
+#[derive(Debug, Clone, Deserialize)]
+struct ApiErrorResponse {
+    error: String,
+    #[serde(rename = "errorMessage")]
+    error_message: String,
+}
+
+/// Deserializes a JSON response into `T`, first checking the HTTP status
+/// and returning [`Error::Api`] (parsed from Mojang's `{error, errorMessage}`
+/// error shape) if it does not indicate success.
 ///
-/// The server hash can be retrieved using [`server_hash`](fn.server_hash.html).
-/// Obtaining it requires the server's public RSA key and the secret key
-/// being used for encryption with the client.
+/// A success response with an empty body (as Mojang returns for some "no
+/// such resource" cases) yields `Ok(None)`, letting callers decide what that
+/// means, e.g. [`Error::NoSuchPlayer`].
 ///
-/// Performing this request also requires the client's username.
-/// Servers should use the value sent in the Login Start packet.
+/// This is shared by every endpoint in the crate, including ones that
+/// return credentials (access tokens, `clientToken`) and account PII, so it
+/// only traces the status and body size, never the body itself. Endpoints
+/// whose response is not sensitive (e.g. [`MojangClient::server_auth`]) may
+/// trace their own response body individually.
+async fn parse_json_response<T>(response: reqwest::Response) -> Result<Option<T>>
+where
+    T: serde::de::DeserializeOwned,
+{
+    let status = response.status();
+    let text = response.text().await.map_err(Error::Http)?;
+
+    trace!("Response ({}): {} bytes", status, text.len());
+
+    if status.is_success() {
+        if text.is_empty() {
+            return Ok(None);
+        }
+        return serde_json::from_str(&text).map(Some).map_err(Error::Json);
+    }
+
+    Err(parse_api_error(status, &text)?)
+}
+
+/// Parses Mojang's `{error, errorMessage}` error shape out of a non-success
+/// response body, returning the resulting [`Error::Api`].
+fn parse_api_error(status: StatusCode, text: &str) -> Result<Error> {
+    let api_error: ApiErrorResponse = serde_json::from_str(text).map_err(Error::Json)?;
+
+    Ok(Error::Api {
+        status: status.as_u16(),
+        error: api_error.error,
+        error_message: api_error.error_message,
+    })
+}
+
+/// The default request timeout used by a [`MojangClient`], if none is set
+/// through [`Endpoints::timeout`].
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The base URLs and request timeout used by a [`MojangClient`] to reach
+/// Mojang's various services.
 ///
-/// The request is performed asynchronously, and this function is `async`.
+/// The defaults point at Mojang's production services; override them to
+/// point a [`MojangClient`] at a mojang-compatible proxy or mirror (for
+/// example, an authlib-injector server).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Endpoints {
+    /// Base URL of the session server, used for `server_auth`/`client_auth`.
+    pub session_server: String,
+    /// Base URL of the authentication server, used for
+    /// `client_login`/`validate_token`/`refresh_token`.
+    pub auth_server: String,
+    /// Base URL of the profile/services API, used for the username/UUID
+    /// lookup functions.
+    pub services_api: String,
+    /// Maximum time to wait for a request (including connecting) to
+    /// complete, applied to every request made through a [`MojangClient`].
+    /// Defaults to 10 seconds, so a hung or slow-loris proxy cannot block a
+    /// caller forever.
+    pub timeout: Duration,
+}
+
+#[cfg(not(test))]
+impl Default for Endpoints {
+    fn default() -> Self {
+        Endpoints {
+            session_server: String::from("https://sessionserver.mojang.com"),
+            auth_server: String::from("https://authserver.mojang.com"),
+            services_api: String::from("https://api.mojang.com"),
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+}
+
+#[cfg(test)]
+impl Default for Endpoints {
+    fn default() -> Self {
+        let url = mockito::server_url();
+        Endpoints {
+            session_server: url.clone(),
+            auth_server: url.clone(),
+            services_api: url,
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+}
+
+/// A reusable client for the Mojang API.
 ///
-/// See [wiki.vg](https://wiki.vg/Protocol_Encryption#Server) for more
-/// information.
+/// `MojangClient` owns a single [`reqwest::Client`], so requests made
+/// through it benefit from connection pooling and TLS session reuse. The
+/// free functions in this crate all go through one shared,
+/// lazily-initialized default instance of this struct. Construct one
+/// `MojangClient` per application and share it across requests, rather
+/// than creating one per request.
+///
+/// The base URLs and request timeout can be overridden through
+/// [`MojangClient::with_endpoints`], which is useful for testing or for
+/// pointing at a mojang-compatible proxy.
+#[derive(Debug, Clone)]
+pub struct MojangClient {
+    http: Client,
+    endpoints: Endpoints,
+}
+
+impl MojangClient {
+    /// Creates a new `MojangClient` using Mojang's production endpoints.
+    pub fn new() -> Self {
+        MojangClient::with_endpoints(Endpoints::default())
+    }
+
+    /// Creates a new `MojangClient` using the given endpoints.
+    pub fn with_endpoints(endpoints: Endpoints) -> Self {
+        let http = Client::builder()
+            .timeout(endpoints.timeout)
+            .build()
+            .expect("failed to build the underlying reqwest client");
+
+        MojangClient { http, endpoints }
+    }
+
+    /// The endpoints this client sends requests to.
+    pub fn endpoints(&self) -> &Endpoints {
+        &self.endpoints
+    }
+
+    /// Performs server-side authentication using the given server hash
+    /// and username.
+    ///
+    /// The server hash can be retrieved using [`server_hash`](fn.server_hash.html).
+    /// Obtaining it requires the server's public RSA key and the secret key
+    /// being used for encryption with the client.
+    ///
+    /// Performing this request also requires the client's username.
+    /// Servers should use the value sent in the Login Start packet.
+    ///
+    /// See [wiki.vg](https://wiki.vg/Protocol_Encryption#Server) for more
+    /// information.
+    ///
+    /// Returns `Err(Error::AuthenticationFailed)` if the session server
+    /// reports that the client did not authenticate correctly (an empty
+    /// response with HTTP 204), or `Err(Error::Api)` for any other
+    /// non-success response, e.g. a rate limit.
+    pub async fn server_auth(
+        &self,
+        server_hash: &str,
+        username: &str,
+    ) -> Result<ServerAuthResponse> {
+        let url = format!(
+            "{}/session/minecraft/hasJoined?username={}&serverId={}&unsigned=false",
+            self.endpoints.session_server, username, server_hash
+        );
+
+        let response = self.http.get(&url).send().await.map_err(Error::Http)?;
+
+        if response.status() == StatusCode::NO_CONTENT {
+            return Err(Error::AuthenticationFailed);
+        }
+
+        let status = response.status();
+        let text = response.text().await.map_err(Error::Http)?;
+
+        // Unlike most other endpoints, `hasJoined`'s response (a UUID,
+        // username, and signed texture properties) isn't sensitive, so it's
+        // safe to trace in full here.
+        trace!("hasJoined response ({}): {}", status, text);
+
+        if !status.is_success() {
+            return Err(parse_api_error(status, &text)?);
+        }
+        if text.is_empty() {
+            return Err(Error::InvalidResponse("empty response body".to_string()));
+        }
+        serde_json::from_str(&text).map_err(Error::Json)
+    }
+
+    /// Authenticates a user, returning a client access token and metadata
+    /// for the user.
+    ///
+    /// `client_token` should be a stable identifier generated once by the
+    /// caller (e.g. a random UUID) and persisted alongside the access
+    /// token: Mojang invalidates access tokens minted without a stable
+    /// client token when [`MojangClient::refresh_token`] or
+    /// [`MojangClient::validate_token`] is later called with a different
+    /// one.
+    ///
+    /// The returned access token can later be used with
+    /// [`MojangClient::client_auth`] to log in to a server.
+    ///
+    /// Returns `Err(Error::Api)` if authentication fails, e.g. with a wrong
+    /// password (`ForbiddenOperationException`, HTTP 403) or after being
+    /// rate-limited (HTTP 429).
+    pub async fn client_login(
+        &self,
+        username: &str,
+        password: &str,
+        client_token: &str,
+    ) -> Result<ClientLoginResponse> {
+        let url = format!("{}/authenticate", self.endpoints.auth_server);
+
+        let payload = json!({
+            "agent": {
+                "name": "Minecraft",
+                "version": 1
+            },
+            "username": username,
+            "password": password,
+            "clientToken": client_token,
+            "requestUser": true
+        })
+        .to_string();
+
+        let response = self
+            .http
+            .post(&url)
+            .body(payload)
+            .send()
+            .await
+            .map_err(Error::Http)?;
+
+        parse_json_response(response)
+            .await?
+            .ok_or_else(|| Error::InvalidResponse("empty response body".to_string()))
+    }
+
+    /// Checks whether an access token obtained from
+    /// [`MojangClient::client_login`] (or refreshed with
+    /// [`MojangClient::refresh_token`]) is still valid, without invalidating
+    /// it.
+    ///
+    /// `client_token` must be the same client token used to obtain the
+    /// access token.
+    ///
+    /// Returns `Ok(false)` if the token is invalid (HTTP 403), or
+    /// `Err(Error::Api)` for any other non-success response, e.g. a rate
+    /// limit.
+    pub async fn validate_token(&self, access_token: &str, client_token: &str) -> Result<bool> {
+        let url = format!("{}/validate", self.endpoints.auth_server);
+
+        let payload = json!({
+            "accessToken": access_token,
+            "clientToken": client_token
+        })
+        .to_string();
+
+        let response = self
+            .http
+            .post(&url)
+            .body(payload)
+            .send()
+            .await
+            .map_err(Error::Http)?;
+
+        if response.status() == StatusCode::NO_CONTENT {
+            return Ok(true);
+        }
+        if response.status() == StatusCode::FORBIDDEN {
+            return Ok(false);
+        }
+
+        parse_json_response::<serde::de::IgnoredAny>(response).await?;
+
+        Ok(false)
+    }
+
+    /// Refreshes an access token obtained from
+    /// [`MojangClient::client_login`], invalidating it and returning a new
+    /// one, without requiring the user's password again.
+    ///
+    /// `client_token` must be the same client token used to obtain the
+    /// access token, and is echoed back in the response.
+    pub async fn refresh_token(
+        &self,
+        access_token: &str,
+        client_token: &str,
+    ) -> Result<ClientLoginResponse> {
+        let url = format!("{}/refresh", self.endpoints.auth_server);
+
+        let payload = json!({
+            "accessToken": access_token,
+            "clientToken": client_token,
+            "requestUser": true
+        })
+        .to_string();
+
+        let response = self
+            .http
+            .post(&url)
+            .body(payload)
+            .send()
+            .await
+            .map_err(Error::Http)?;
+
+        parse_json_response(response)
+            .await?
+            .ok_or_else(|| Error::InvalidResponse("empty response body".to_string()))
+    }
+
+    /// Performs client-side authentication with the given access
+    /// token and server hash.
+    ///
+    /// The access token can be obtained using
+    /// [`MojangClient::client_login`]; the server hash can be computed with
+    /// [`server_hash`](fn.server_hash.html).
+    ///
+    /// This API endpoint returns no response. If all goes well,
+    /// then no error will be returned, and the client can proceed
+    /// with the login process. Otherwise, returns `Err(Error::Api)`, e.g.
+    /// with a `ForbiddenOperationException` (HTTP 403) if the access token
+    /// is invalid.
+    pub async fn client_auth(
+        &self,
+        access_token: &str,
+        uuid: Uuid,
+        server_hash: &str,
+    ) -> Result<()> {
+        let url = format!("{}/session/minecraft/join", self.endpoints.session_server);
+
+        let payload = json!({
+            "accessToken": access_token,
+            "selectedProfile": uuid,
+            "serverId": server_hash
+        });
+
+        let response = self
+            .http
+            .post(&url)
+            .body(payload.to_string())
+            .send()
+            .await
+            .map_err(Error::Http)?;
+
+        parse_json_response::<serde::de::IgnoredAny>(response).await?;
+
+        Ok(())
+    }
+
+    /// Resolves a username to its current UUID and canonical
+    /// (correctly-cased) username.
+    ///
+    /// Returns `Err(Error::NoSuchPlayer)` if no player exists with that
+    /// username, or `Err(Error::Api)` if the request is otherwise rejected,
+    /// e.g. after being rate-limited (HTTP 429).
+    pub async fn username_to_uuid(&self, username: &str) -> Result<NameUuid> {
+        let url = format!(
+            "{}/users/profiles/minecraft/{}",
+            self.endpoints.services_api, username
+        );
+
+        let response = self.http.get(&url).send().await.map_err(Error::Http)?;
+
+        parse_json_response(response)
+            .await?
+            .ok_or(Error::NoSuchPlayer)
+    }
+
+    /// Resolves a batch of usernames to their current UUIDs and canonical
+    /// usernames. Usernames with no matching player are simply omitted from
+    /// the result, rather than causing an error.
+    ///
+    /// Larger inputs are chunked internally into requests of at most
+    /// [`USERNAMES_PER_REQUEST`] usernames each, since that is the limit
+    /// Mojang enforces.
+    ///
+    /// Returns `Err(Error::Api)` if a request is rejected, e.g. after being
+    /// rate-limited (HTTP 429).
+    pub async fn usernames_to_uuids(&self, usernames: &[&str]) -> Result<Vec<NameUuid>> {
+        let url = format!("{}/profiles/minecraft", self.endpoints.services_api);
+
+        let mut results = Vec::with_capacity(usernames.len());
+
+        for chunk in usernames.chunks(USERNAMES_PER_REQUEST) {
+            let payload = serde_json::to_string(chunk).map_err(Error::Json)?;
+
+            let response = self
+                .http
+                .post(&url)
+                .body(payload)
+                .send()
+                .await
+                .map_err(Error::Http)?;
+
+            if let Some(chunk_results) = parse_json_response::<Vec<NameUuid>>(response).await? {
+                results.extend(chunk_results);
+            }
+        }
+
+        Ok(results)
+    }
+}
+
+impl Default for MojangClient {
+    fn default() -> Self {
+        MojangClient::new()
+    }
+}
+
+/// The lazily-initialized default [`MojangClient`] backing the free
+/// functions in this crate.
+static DEFAULT_CLIENT: Lazy<MojangClient> = Lazy::new(MojangClient::new);
+
+/// Performs server-side authentication using the given server hash
+/// and username.
+///
+/// This is a thin wrapper around [`MojangClient::server_auth`] using a
+/// lazily-initialized default client; see that method for more details.
 ///
 /// # Examples
 /// ```no_run
@@ -165,28 +759,7 @@ pub struct ProfileProperty {
 /// # }
 /// ```
 pub async fn server_auth(server_hash: &str, username: &str) -> Result<ServerAuthResponse> {
-    #[cfg(not(test))]
-        let url = format!(
-        "https://sessionserver.mojang.com/session/minecraft/hasJoined?username={}&serverId={}&unsigned=false",
-        username, server_hash
-    );
-    #[cfg(test)]
-    let url = format!("{}/{}/{}", mockito::server_url(), username, server_hash,);
-
-    let string = Client::new()
-        .get(&url)
-        .send()
-        .await
-        .map_err(Error::Http)?
-        .text()
-        .await
-        .map_err(Error::Http)?;
-
-    trace!("Authentication response: {}", string);
-
-    let response = serde_json::from_str(&string).map_err(Error::Json)?;
-
-    Ok(response)
+    DEFAULT_CLIENT.server_auth(server_hash, username).await
 }
 
 /// Computes the "server hash" required for authentication
@@ -237,10 +810,15 @@ pub fn hexdigest(hasher: &Sha1) -> String {
 /// The response includes an access token, used for client-side authentication,
 /// as well as information about the user which was authenticated.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
 pub struct ClientLoginResponse {
     /// The access token which can later be used for client-side authentication
     /// when logging into a server.
     pub access_token: String,
+    /// The client token submitted with the request (or generated by Mojang,
+    /// if none was submitted). Refreshing or validating the access token
+    /// later requires this same client token.
+    pub client_token: String,
     /// Contains information about the user which authenticated.
     pub user: User,
 }
@@ -286,47 +864,81 @@ pub struct User {
 
 /// Authenticates a user, returning a client access token and metadata for the user.
 ///
+/// `client_token` should be a stable identifier generated once by the caller
+/// (e.g. a random UUID) and persisted alongside the access token: Mojang
+/// invalidates access tokens minted without a stable client token when
+/// [`refresh_token`] or [`validate_token`] is later called with a different
+/// one.
+///
 /// The returned access token can later be used with `client_auth` to log in to a server.
 ///
+/// Returns `Err(Error::Api)` if authentication fails, e.g. with a wrong
+/// password (`ForbiddenOperationException`, HTTP 403) or after being
+/// rate-limited (HTTP 429).
+///
 /// # Examples
 /// ```no_run
 /// # #[tokio::main]
 /// # async fn main() -> mojang_api::Result<()> {
-/// let response: mojang_api::ClientLoginResponse = mojang_api::client_login("username", "password").await?;
+/// let response: mojang_api::ClientLoginResponse =
+///     mojang_api::client_login("username", "password", "client_token").await?;
 /// println!("Access token: {}", response.access_token);
 /// println!("User email: {}", response.user.email);
 /// # Ok(())
 /// # }
 /// ```
-pub async fn client_login(username: &str, password: &str) -> Result<ClientLoginResponse> {
-    #[cfg(test)]
-    let url = format!("{}/authenticate", mockito::server_url());
-    #[cfg(not(test))]
-    let url = String::from("https://authserver.mojang.com/authenticate");
-
-    let payload = json!({
-        "agent": {
-            "name": "Minecraft",
-            "version": 1
-        },
-        "username": username,
-        "password": password,
-        "requestUser": true
-    })
-    .to_string();
-
-    let client = Client::new();
-    let response = client
-        .post(&url)
-        .body(payload)
-        .send()
+pub async fn client_login(
+    username: &str,
+    password: &str,
+    client_token: &str,
+) -> Result<ClientLoginResponse> {
+    DEFAULT_CLIENT
+        .client_login(username, password, client_token)
         .await
-        .map_err(Error::Http)?
-        .text()
+}
+
+/// Checks whether an access token obtained from [`client_login`] (or
+/// refreshed with [`refresh_token`]) is still valid, without invalidating it.
+///
+/// `client_token` must be the same client token used to obtain the access
+/// token.
+///
+/// Returns `Ok(false)` if the token is invalid (HTTP 403), or
+/// `Err(Error::Api)` for any other non-success response, e.g. a rate limit.
+///
+/// # Examples
+/// ```no_run
+/// # #[tokio::main]
+/// # async fn main() -> mojang_api::Result<()> {
+/// let still_valid = mojang_api::validate_token("access_token", "client_token").await?;
+/// # Ok(())
+/// # }
+/// ```
+pub async fn validate_token(access_token: &str, client_token: &str) -> Result<bool> {
+    DEFAULT_CLIENT
+        .validate_token(access_token, client_token)
         .await
-        .map_err(Error::Http)?;
+}
 
-    serde_json::from_str(&response).map_err(Error::Json)
+/// Refreshes an access token obtained from [`client_login`], invalidating it
+/// and returning a new one, without requiring the user's password again.
+///
+/// `client_token` must be the same client token used to obtain the access
+/// token, and is echoed back in the response.
+///
+/// # Examples
+/// ```no_run
+/// # #[tokio::main]
+/// # async fn main() -> mojang_api::Result<()> {
+/// let response = mojang_api::refresh_token("access_token", "client_token").await?;
+/// println!("New access token: {}", response.access_token);
+/// # Ok(())
+/// # }
+/// ```
+pub async fn refresh_token(access_token: &str, client_token: &str) -> Result<ClientLoginResponse> {
+    DEFAULT_CLIENT
+        .refresh_token(access_token, client_token)
+        .await
 }
 
 /// Performs client-side authentication with the given access
@@ -342,7 +954,7 @@ pub async fn client_login(username: &str, password: &str) -> Result<ClientLoginR
 /// # Examples
 /// ```no_run
 /// # #[tokio::main] async fn main() -> mojang_api::Result<()> {
-/// let login = mojang_api::client_login("username", "password").await?;
+/// let login = mojang_api::client_login("username", "password", "client_token").await?;
 /// let server_hash = mojang_api::server_hash("", [0u8; 16], &[1]);
 ///
 /// mojang_api::client_auth(&login.access_token, login.user.id, &server_hash);
@@ -350,31 +962,76 @@ pub async fn client_login(username: &str, password: &str) -> Result<ClientLoginR
 /// # }
 /// ```
 pub async fn client_auth(access_token: &str, uuid: Uuid, server_hash: &str) -> Result<()> {
-    #[cfg(not(test))]
-    let url = String::from("https://sessionserver.mojang.com/session/minecraft/join");
-    #[cfg(test)]
-    let url = mockito::server_url();
-
-    let payload = json!({
-        "accessToken": access_token,
-        "selectedProfile": uuid,
-        "serverId": server_hash
-    });
-
-    let client = Client::new();
-    client
-        .post(&url)
-        .body(payload.to_string())
-        .send()
+    DEFAULT_CLIENT
+        .client_auth(access_token, uuid, server_hash)
         .await
-        .map_err(Error::Http)?;
+}
+
+/// A username/UUID pair, as returned by the profile lookup endpoints.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct NameUuid {
+    /// The UUID of the player.
+    pub id: Uuid,
+    /// The current username of the player.
+    pub name: String,
+}
+
+/// Resolves a username to its current UUID and canonical (correctly-cased)
+/// username.
+///
+/// Returns `Err(Error::NoSuchPlayer)` if no player exists with that username,
+/// or `Err(Error::Api)` if the request is otherwise rejected, e.g. after
+/// being rate-limited (HTTP 429).
+///
+/// # Examples
+/// ```no_run
+/// # #[tokio::main]
+/// # async fn main() -> mojang_api::Result<()> {
+/// let profile = mojang_api::username_to_uuid("Notch").await?;
+/// println!("Notch's UUID is {}", profile.id);
+/// # Ok(())
+/// # }
+/// ```
+pub async fn username_to_uuid(username: &str) -> Result<NameUuid> {
+    DEFAULT_CLIENT.username_to_uuid(username).await
+}
+
+/// The maximum number of usernames Mojang accepts in a single
+/// [`usernames_to_uuids`] request.
+const USERNAMES_PER_REQUEST: usize = 10;
 
-    Ok(())
+/// Resolves a batch of usernames to their current UUIDs and canonical
+/// usernames. Usernames with no matching player are simply omitted from
+/// the result, rather than causing an error.
+///
+/// Larger inputs are chunked internally into requests of at most 10
+/// usernames each, since that is the limit Mojang enforces.
+///
+/// Returns `Err(Error::Api)` if a request is rejected, e.g. after being
+/// rate-limited (HTTP 429).
+///
+/// # Examples
+/// ```no_run
+/// # #[tokio::main]
+/// # async fn main() -> mojang_api::Result<()> {
+/// let profiles = mojang_api::usernames_to_uuids(&["Notch", "jeb_"]).await?;
+/// for profile in profiles {
+///     println!("{} => {}", profile.name, profile.id);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub async fn usernames_to_uuids(usernames: &[&str]) -> Result<Vec<NameUuid>> {
+    DEFAULT_CLIENT.usernames_to_uuids(usernames).await
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use mockito::Matcher;
+    use rand::rngs::OsRng;
+    use rsa::pkcs8::EncodePublicKey;
+    use rsa::RsaPrivateKey;
     use std::io::ErrorKind;
     use uuid::Uuid;
 
@@ -415,6 +1072,83 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_decode_textures() -> Result<()> {
+        let inner_json = serde_json::json!({
+            "timestamp": 1_600_000_000_000u64,
+            "profileId": "00000000000000000000000000000000",
+            "profileName": "Notch",
+            "textures": {
+                "SKIN": {
+                    "url": "http://textures.minecraft.net/texture/skin",
+                    "metadata": { "model": "slim" }
+                },
+                "CAPE": {
+                    "url": "http://textures.minecraft.net/texture/cape"
+                }
+            }
+        })
+        .to_string();
+
+        let property = ProfileProperty {
+            name: "textures".to_string(),
+            value: base64::encode(inner_json),
+            signature: "irrelevant_for_decoding".to_string(),
+        };
+
+        let textures = property.decode_textures()?;
+
+        assert_eq!(
+            textures.skin_url,
+            "http://textures.minecraft.net/texture/skin"
+        );
+        assert_eq!(textures.skin_model, Some(SkinModel::Slim));
+        assert_eq!(
+            textures.cape_url,
+            Some("http://textures.minecraft.net/texture/cape".to_string())
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_verify_signature_invalid_key() {
+        let property = ProfileProperty {
+            name: "textures".to_string(),
+            value: base64::encode("not real textures data"),
+            signature: base64::encode("not a real signature"),
+        };
+
+        assert!(!property.verify_signature(b"not a real DER-encoded public key"));
+    }
+
+    #[test]
+    fn test_verify_signature_valid() {
+        let mut rng = OsRng;
+        let private_key = RsaPrivateKey::new(&mut rng, 1024).expect("failed to generate RSA key");
+        let public_key = RsaPublicKey::from(&private_key);
+        let public_key_der = public_key.to_public_key_der().unwrap();
+
+        let value = base64::encode(b"some texture payload");
+
+        let mut hasher = Sha1::new();
+        hasher.update(value.as_bytes());
+        let digest = hasher.digest().bytes();
+
+        let padding = PaddingScheme::new_pkcs1v15_sign(Some(Hash::SHA1));
+        let signature = private_key
+            .sign(padding, &digest)
+            .expect("failed to sign digest");
+
+        let property = ProfileProperty {
+            name: "textures".to_string(),
+            value,
+            signature: base64::encode(signature),
+        };
+
+        assert!(property.verify_signature(public_key_der.as_ref()));
+    }
+
     #[tokio::test]
     async fn test_server_auth() -> Result<()> {
         let uuid = Uuid::new_v4();
@@ -438,9 +1172,16 @@ mod tests {
         println!("{}", serde_json::to_string(&response).unwrap());
 
         let hash = server_hash("", [0; 16], &[0]);
-        let _m = mockito::mock("GET", format!("/{}/{}", username, hash).as_str())
-            .with_body(serde_json::to_string(&response).unwrap())
-            .create();
+        let _m = mockito::mock(
+            "GET",
+            format!(
+                "/session/minecraft/hasJoined?username={}&serverId={}&unsigned=false",
+                username, hash
+            )
+            .as_str(),
+        )
+        .with_body(serde_json::to_string(&response).unwrap())
+        .create();
 
         let result = server_auth(&hash, username).await?;
 
@@ -457,12 +1198,90 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_server_auth_authentication_failed() {
+        let username = "test_";
+        let hash = server_hash("", [0; 16], &[0]);
+
+        let _m = mockito::mock(
+            "GET",
+            format!(
+                "/session/minecraft/hasJoined?username={}&serverId={}&unsigned=false",
+                username, hash
+            )
+            .as_str(),
+        )
+        .with_status(204)
+        .create();
+
+        let result = server_auth(&hash, username).await;
+
+        assert_eq!(result.unwrap_err(), Error::AuthenticationFailed);
+    }
+
+    #[tokio::test]
+    async fn test_mojang_client_custom_endpoints() -> Result<()> {
+        let expected = NameUuid {
+            id: Uuid::new_v4(),
+            name: "Notch".to_string(),
+        };
+
+        let _m = mockito::mock("GET", "/users/profiles/minecraft/Notch")
+            .with_body(serde_json::to_string(&expected).unwrap())
+            .create();
+
+        let client = MojangClient::with_endpoints(Endpoints {
+            session_server: mockito::server_url(),
+            auth_server: mockito::server_url(),
+            services_api: mockito::server_url(),
+            ..Endpoints::default()
+        });
+        let result = client.username_to_uuid("Notch").await?;
+
+        assert_eq!(result, expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_mojang_client_custom_timeout() {
+        let client = MojangClient::with_endpoints(Endpoints {
+            timeout: Duration::from_secs(1),
+            ..Endpoints::default()
+        });
+
+        assert_eq!(client.endpoints().timeout, Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn test_mojang_client_timeout_is_enforced() {
+        // A listener that accepts connections but never writes a response, so
+        // any request against it hangs until the client's timeout elapses.
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            let _ = listener.accept();
+            std::thread::sleep(Duration::from_secs(5));
+        });
+
+        let client = MojangClient::with_endpoints(Endpoints {
+            session_server: format!("http://{}", addr),
+            timeout: Duration::from_millis(50),
+            ..Endpoints::default()
+        });
+
+        let result = client.server_auth("hash", "username").await;
+
+        assert!(matches!(result, Err(Error::Http(_))));
+    }
+
     #[tokio::test]
     async fn test_client_login() {
         let expected_response = ClientLoginResponse {
             access_token: String::from("test_29408"),
+            client_token: String::from("test_client_token"),
             user: User {
-                id: Uuid::new_v4(),
+                id: "00000000-0000-0000-0000-000000000001".parse().unwrap(),
                 email: "test@example.com".to_string(),
                 username: "test".to_string(),
                 register_ip: "127.0.0.*".to_string(),
@@ -481,12 +1300,284 @@ mod tests {
             },
         };
 
+        // A real `/authenticate` response shape (camelCase field names),
+        // rather than a re-serialization of `expected_response`, so that a
+        // missing `#[serde(rename_all = "camelCase")]` would actually fail
+        // this test.
+        let body = r#"{
+            "accessToken": "test_29408",
+            "clientToken": "test_client_token",
+            "user": {
+                "id": "00000000-0000-0000-0000-000000000001",
+                "email": "test@example.com",
+                "username": "test",
+                "registerIp": "127.0.0.*",
+                "registeredAt": 354,
+                "passwordChangedAt": 249,
+                "dateOfBirth": 124,
+                "suspended": false,
+                "blocked": false,
+                "secured": false,
+                "migrated": false,
+                "emailVerified": false,
+                "legacyUser": false,
+                "verifiedByParent": false
+            }
+        }"#;
+
         let _m = mockito::mock("POST", "/authenticate")
-            .with_body(serde_json::to_string(&expected_response).unwrap())
+            .with_body(body)
             .create();
 
-        let response = client_login("test", "password").await.unwrap();
+        let response = client_login("test", "password", "test_client_token")
+            .await
+            .unwrap();
 
         assert_eq!(response, expected_response);
     }
+
+    #[tokio::test]
+    async fn test_client_login_api_error() {
+        let _m = mockito::mock("POST", "/authenticate")
+            .with_status(403)
+            .with_body(
+                serde_json::json!({
+                    "error": "ForbiddenOperationException",
+                    "errorMessage": "Invalid credentials."
+                })
+                .to_string(),
+            )
+            .create();
+
+        let result = client_login("test", "wrong_password", "test_client_token").await;
+
+        assert_eq!(
+            result.unwrap_err(),
+            Error::Api {
+                status: 403,
+                error: "ForbiddenOperationException".to_string(),
+                error_message: "Invalid credentials.".to_string(),
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_validate_token_valid() -> Result<()> {
+        let _m = mockito::mock("POST", "/validate").with_status(204).create();
+
+        let valid = validate_token("access_token", "client_token").await?;
+
+        assert!(valid);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_validate_token_invalid() -> Result<()> {
+        let _m = mockito::mock("POST", "/validate").with_status(403).create();
+
+        let valid = validate_token("access_token", "client_token").await?;
+
+        assert!(!valid);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_validate_token_rate_limited() {
+        let _m = mockito::mock("POST", "/validate")
+            .with_status(429)
+            .with_body(
+                r#"{"error": "TooManyRequestsException", "errorMessage": "Too many requests"}"#,
+            )
+            .create();
+
+        let result = validate_token("access_token", "client_token").await;
+
+        assert_eq!(
+            result.unwrap_err(),
+            Error::Api {
+                status: 429,
+                error: "TooManyRequestsException".to_string(),
+                error_message: "Too many requests".to_string(),
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_refresh_token() -> Result<()> {
+        let expected_response = ClientLoginResponse {
+            access_token: String::from("new_access_token"),
+            client_token: String::from("test_client_token"),
+            user: User {
+                id: "00000000-0000-0000-0000-000000000001".parse().unwrap(),
+                email: "test@example.com".to_string(),
+                username: "test".to_string(),
+                register_ip: "127.0.0.*".to_string(),
+                migrated_from: None,
+                migrated_at: None,
+                registered_at: 0354,
+                password_changed_at: Some(249),
+                date_of_birth: 124,
+                suspended: false,
+                blocked: false,
+                secured: false,
+                migrated: false,
+                email_verified: false,
+                legacy_user: false,
+                verified_by_parent: false,
+            },
+        };
+
+        // A real `/refresh` response shape (camelCase field names), rather
+        // than a re-serialization of `expected_response`, so that a missing
+        // `#[serde(rename_all = "camelCase")]` would actually fail this test.
+        let body = r#"{
+            "accessToken": "new_access_token",
+            "clientToken": "test_client_token",
+            "user": {
+                "id": "00000000-0000-0000-0000-000000000001",
+                "email": "test@example.com",
+                "username": "test",
+                "registerIp": "127.0.0.*",
+                "registeredAt": 354,
+                "passwordChangedAt": 249,
+                "dateOfBirth": 124,
+                "suspended": false,
+                "blocked": false,
+                "secured": false,
+                "migrated": false,
+                "emailVerified": false,
+                "legacyUser": false,
+                "verifiedByParent": false
+            }
+        }"#;
+
+        let _m = mockito::mock("POST", "/refresh").with_body(body).create();
+
+        let response = refresh_token("access_token", "test_client_token").await?;
+
+        assert_eq!(response, expected_response);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_username_to_uuid() -> Result<()> {
+        let expected = NameUuid {
+            id: Uuid::new_v4(),
+            name: "Notch".to_string(),
+        };
+
+        let _m = mockito::mock("GET", "/users/profiles/minecraft/Notch")
+            .with_body(serde_json::to_string(&expected).unwrap())
+            .create();
+
+        let result = username_to_uuid("Notch").await?;
+
+        assert_eq!(result, expected);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_username_to_uuid_no_such_player() {
+        let _m = mockito::mock("GET", "/users/profiles/minecraft/xXNoSuchPlayerXx")
+            .with_status(204)
+            .create();
+
+        let result = username_to_uuid("xXNoSuchPlayerXx").await;
+
+        assert_eq!(result.unwrap_err(), Error::NoSuchPlayer);
+    }
+
+    #[tokio::test]
+    async fn test_username_to_uuid_api_error() {
+        let _m = mockito::mock("GET", "/users/profiles/minecraft/test_")
+            .with_status(429)
+            .with_body(
+                serde_json::json!({
+                    "error": "TooManyRequestsException",
+                    "errorMessage": "The client has sent too many requests."
+                })
+                .to_string(),
+            )
+            .create();
+
+        let result = username_to_uuid("test_").await;
+
+        assert_eq!(
+            result.unwrap_err(),
+            Error::Api {
+                status: 429,
+                error: "TooManyRequestsException".to_string(),
+                error_message: "The client has sent too many requests.".to_string(),
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_usernames_to_uuids() -> Result<()> {
+        let expected = vec![
+            NameUuid {
+                id: Uuid::new_v4(),
+                name: "Notch".to_string(),
+            },
+            NameUuid {
+                id: Uuid::new_v4(),
+                name: "jeb_".to_string(),
+            },
+        ];
+
+        let _m = mockito::mock("POST", "/profiles/minecraft")
+            .with_body(serde_json::to_string(&expected).unwrap())
+            .create();
+
+        let result = usernames_to_uuids(&["Notch", "jeb_"]).await?;
+
+        assert_eq!(result, expected);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_usernames_to_uuids_chunks_large_inputs() -> Result<()> {
+        let usernames: Vec<String> = (0..12).map(|i| format!("player{}", i)).collect();
+        let usernames: Vec<&str> = usernames.iter().map(String::as_str).collect();
+
+        let first_chunk: Vec<NameUuid> = usernames[..10]
+            .iter()
+            .map(|name| NameUuid {
+                id: Uuid::new_v4(),
+                name: name.to_string(),
+            })
+            .collect();
+        let second_chunk: Vec<NameUuid> = usernames[10..]
+            .iter()
+            .map(|name| NameUuid {
+                id: Uuid::new_v4(),
+                name: name.to_string(),
+            })
+            .collect();
+
+        let _m1 = mockito::mock("POST", "/profiles/minecraft")
+            .match_body(Matcher::Json(
+                serde_json::to_value(&usernames[..10]).unwrap(),
+            ))
+            .with_body(serde_json::to_string(&first_chunk).unwrap())
+            .create();
+        let _m2 = mockito::mock("POST", "/profiles/minecraft")
+            .match_body(Matcher::Json(
+                serde_json::to_value(&usernames[10..]).unwrap(),
+            ))
+            .with_body(serde_json::to_string(&second_chunk).unwrap())
+            .create();
+
+        let result = usernames_to_uuids(&usernames).await?;
+
+        let expected: Vec<NameUuid> = first_chunk.into_iter().chain(second_chunk).collect();
+        assert_eq!(result, expected);
+
+        Ok(())
+    }
 }
@@ -0,0 +1,789 @@
+//! Microsoft/Xbox Live authentication, the replacement for the legacy
+//! Yggdrasil [`client_login`](crate::client_login) flow.
+//!
+//! Authenticating with a Microsoft account requires walking through several
+//! services in sequence:
+//!
+//! 1. Obtain a device code from Microsoft's OAuth endpoint and have the user
+//!    authorize it in a browser ([`request_device_code`](MicrosoftAuth::request_device_code)),
+//!    then poll for the resulting tokens
+//!    ([`poll_device_code_token`](MicrosoftAuth::poll_device_code_token)).
+//! 2. Exchange the Microsoft access token for an Xbox Live token
+//!    ([`authenticate_xbox_live`](MicrosoftAuth::authenticate_xbox_live)).
+//! 3. Exchange the Xbox Live token for an XSTS token
+//!    ([`authenticate_xsts`](MicrosoftAuth::authenticate_xsts)).
+//! 4. Exchange the XSTS token for a Minecraft access token
+//!    ([`login_with_xbox`](MicrosoftAuth::login_with_xbox)).
+//! 5. Fetch the Minecraft profile ([`profile`](MicrosoftAuth::profile)).
+//!
+//! [`MicrosoftAuth::finish_device_code_login`] drives steps 2 through 5 for you
+//! once a Microsoft access token has been obtained. The resulting access token
+//! can be used with [`client_auth`](crate::client_auth) exactly like the token
+//! returned by `client_login`.
+//!
+//! # Examples
+//! ```no_run
+//! # #[tokio::main]
+//! # async fn main() -> mojang_api::Result<()> {
+//! use mojang_api::microsoft::MicrosoftAuth;
+//! use std::time::Duration;
+//!
+//! let auth = MicrosoftAuth::new("00000000-0000-0000-0000-000000000000");
+//! let device_code = auth.request_device_code().await?;
+//!
+//! println!(
+//!     "Go to {} and enter the code {}",
+//!     device_code.verification_uri, device_code.user_code
+//! );
+//!
+//! let tokens = auth
+//!     .poll_device_code_token(&device_code.device_code, Duration::from_secs(device_code.interval))
+//!     .await?;
+//!
+//! let (access_token, profile) = auth
+//!     .finish_device_code_login(&tokens.access_token)
+//!     .await?;
+//!
+//! println!("Logged in as {} ({})", profile.name, profile.id);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{parse_json_response, Error, Result};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::convert::TryFrom;
+use std::time::Duration;
+use uuid::Uuid;
+
+/// The OAuth scope requested when signing in to a Microsoft account for Xbox
+/// Live access.
+const SCOPE: &str = "XboxLive.signin offline_access";
+
+/// The grant type used when polling for a device code's tokens.
+const DEVICE_CODE_GRANT_TYPE: &str = "urn:ietf:params:oauth:grant-type:device_code";
+
+/// Amount added to the polling interval each time the server responds with
+/// `slow_down`, per the OAuth 2.0 Device Authorization Grant spec (RFC 8628
+/// section 3.5).
+const SLOW_DOWN_INCREMENT: Duration = Duration::from_secs(5);
+
+/// Response received when requesting a device code from Microsoft's OAuth endpoint.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DeviceCodeResponse {
+    /// The code the device should use to poll for the user's authorization.
+    pub device_code: String,
+    /// The code the user should enter at `verification_uri`.
+    pub user_code: String,
+    /// The URL at which the user can enter `user_code`.
+    pub verification_uri: String,
+    /// Number of seconds until `device_code` expires.
+    pub expires_in: u64,
+    /// Minimum number of seconds to wait between polling requests.
+    pub interval: u64,
+    /// A human-readable message to display to the user, e.g.
+    /// "To sign in, use a web browser...".
+    pub message: String,
+}
+
+/// A set of Microsoft OAuth tokens, received once the user has authorized a
+/// device code (or by refreshing an earlier set of tokens).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct MicrosoftTokenResponse {
+    /// The Microsoft account access token, used to authenticate with Xbox Live.
+    pub access_token: String,
+    /// A refresh token that can be exchanged for a new `access_token` once it expires.
+    pub refresh_token: String,
+    /// Number of seconds until `access_token` expires.
+    pub expires_in: u64,
+}
+
+/// An error response returned while polling for a device code's tokens.
+#[derive(Debug, Clone, Deserialize)]
+struct DeviceCodeErrorResponse {
+    error: String,
+    #[serde(default)]
+    error_description: Option<String>,
+}
+
+/// The shape of an error response returned by the Xbox Live and XSTS
+/// authentication endpoints, e.g. `XErr` 2148916233 for a child account
+/// without parental consent.
+#[derive(Debug, Clone, Deserialize)]
+struct XboxServiceErrorResponse {
+    #[serde(rename = "XErr")]
+    x_err: u64,
+    #[serde(rename = "Message")]
+    message: String,
+}
+
+/// Deserializes a response from the Xbox Live or XSTS endpoints into `T`,
+/// first checking the HTTP status and returning [`Error::Api`] (parsed from
+/// the `{XErr, Message}` error shape those services use) if it does not
+/// indicate success.
+async fn parse_xbox_response<T>(response: reqwest::Response) -> Result<T>
+where
+    T: serde::de::DeserializeOwned,
+{
+    let status = response.status();
+    let text = response.text().await.map_err(Error::Http)?;
+
+    if status.is_success() {
+        return serde_json::from_str(&text).map_err(Error::Json);
+    }
+
+    let api_error: XboxServiceErrorResponse = serde_json::from_str(&text).map_err(Error::Json)?;
+
+    Err(Error::Api {
+        status: status.as_u16(),
+        error: format!("XErr {}", api_error.x_err),
+        error_message: api_error.message,
+    })
+}
+
+/// An Xbox Live (or XSTS) authentication token, together with the user hash
+/// needed to build the `identityToken` sent to the Minecraft services API.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct XboxLiveToken {
+    /// The token itself.
+    pub token: String,
+    /// The user hash (`uhs`) identifying the authenticated user.
+    pub user_hash: String,
+}
+
+/// Raw shape shared by the Xbox Live and XSTS authentication responses.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct XboxServiceResponse {
+    #[serde(rename = "Token")]
+    token: String,
+    #[serde(rename = "DisplayClaims")]
+    display_claims: DisplayClaims,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct DisplayClaims {
+    xui: Vec<XuiClaim>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct XuiClaim {
+    uhs: String,
+}
+
+impl TryFrom<XboxServiceResponse> for XboxLiveToken {
+    type Error = Error;
+
+    fn try_from(response: XboxServiceResponse) -> Result<Self> {
+        let uhs = response
+            .display_claims
+            .xui
+            .first()
+            .ok_or_else(|| {
+                Error::InvalidResponse("missing xui claim in Xbox Live response".to_string())
+            })?
+            .uhs
+            .clone();
+
+        Ok(XboxLiveToken {
+            token: response.token,
+            user_hash: uhs,
+        })
+    }
+}
+
+/// Response received from `minecraftservices.com`'s `login_with_xbox` endpoint.
+#[derive(Debug, Clone, Deserialize)]
+struct MinecraftLoginResponse {
+    access_token: String,
+}
+
+/// A Minecraft profile, as returned by the `minecraft/profile` endpoint.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct MinecraftProfile {
+    /// The UUID of the player.
+    pub id: Uuid,
+    /// The current username of the player.
+    pub name: String,
+}
+
+/// The base URLs and request timeout used by a [`MicrosoftAuth`] to reach
+/// Microsoft's various authentication services.
+///
+/// The defaults point at Microsoft's production services; override them to
+/// point a [`MicrosoftAuth`] at a compatible proxy or mirror, mirroring
+/// [`Endpoints`](crate::Endpoints) for the legacy Yggdrasil API.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MicrosoftEndpoints {
+    /// Base URL of Microsoft's OAuth device-code endpoints, used for
+    /// `request_device_code`/`poll_device_code_token`.
+    pub oauth: String,
+    /// Base URL of the Xbox Live user authentication endpoint, used for
+    /// `authenticate_xbox_live`.
+    pub xbox_live: String,
+    /// Base URL of the XSTS authorization endpoint, used for
+    /// `authenticate_xsts`.
+    pub xsts: String,
+    /// Base URL of the Minecraft services API, used for
+    /// `login_with_xbox`/`profile`.
+    pub minecraft_services: String,
+    /// Maximum time to wait for a request (including connecting) to
+    /// complete, applied to every request made through a [`MicrosoftAuth`].
+    /// Defaults to 10 seconds, so a hung or slow-loris proxy cannot block a
+    /// caller forever.
+    pub timeout: Duration,
+}
+
+#[cfg(not(test))]
+impl Default for MicrosoftEndpoints {
+    fn default() -> Self {
+        MicrosoftEndpoints {
+            oauth: String::from("https://login.microsoftonline.com/consumers/oauth2/v2.0"),
+            xbox_live: String::from("https://user.auth.xboxlive.com"),
+            xsts: String::from("https://xsts.auth.xboxlive.com"),
+            minecraft_services: String::from("https://api.minecraftservices.com"),
+            timeout: crate::DEFAULT_TIMEOUT,
+        }
+    }
+}
+
+#[cfg(test)]
+impl Default for MicrosoftEndpoints {
+    fn default() -> Self {
+        let url = mockito::server_url();
+        MicrosoftEndpoints {
+            oauth: url.clone(),
+            xbox_live: url.clone(),
+            xsts: url.clone(),
+            minecraft_services: url,
+            timeout: crate::DEFAULT_TIMEOUT,
+        }
+    }
+}
+
+/// Implements the Microsoft/Xbox Live authentication chain used to obtain a
+/// Minecraft access token and profile from a Microsoft account.
+///
+/// See the [module-level documentation](self) for an overview of the flow. The
+/// base URLs used for each service can be overridden through
+/// [`MicrosoftAuth::with_endpoints`], which is useful for testing or for
+/// pointing at a compatible proxy.
+#[derive(Debug, Clone)]
+pub struct MicrosoftAuth {
+    client: Client,
+    client_id: String,
+    endpoints: MicrosoftEndpoints,
+}
+
+impl MicrosoftAuth {
+    /// Creates a new `MicrosoftAuth` using the given OAuth application
+    /// (client) ID, registered with Microsoft's identity platform, and
+    /// Microsoft's production endpoints.
+    pub fn new(client_id: impl Into<String>) -> Self {
+        MicrosoftAuth::with_endpoints(client_id, MicrosoftEndpoints::default())
+    }
+
+    /// Creates a new `MicrosoftAuth` using the given client ID and endpoints.
+    pub fn with_endpoints(client_id: impl Into<String>, endpoints: MicrosoftEndpoints) -> Self {
+        let client = Client::builder()
+            .timeout(endpoints.timeout)
+            .build()
+            .expect("failed to build the underlying reqwest client");
+
+        MicrosoftAuth {
+            client,
+            client_id: client_id.into(),
+            endpoints,
+        }
+    }
+
+    /// The endpoints this client sends requests to.
+    pub fn endpoints(&self) -> &MicrosoftEndpoints {
+        &self.endpoints
+    }
+
+    /// Requests a device code, which the user must authorize by visiting
+    /// `verification_uri` and entering `user_code`.
+    ///
+    /// Returns `Err(Error::Api)` if the request is rejected, e.g. because of
+    /// an invalid `client_id`.
+    pub async fn request_device_code(&self) -> Result<DeviceCodeResponse> {
+        let url = format!("{}/devicecode", self.endpoints.oauth);
+
+        let response = self
+            .client
+            .post(&url)
+            .form(&[("client_id", self.client_id.as_str()), ("scope", SCOPE)])
+            .send()
+            .await
+            .map_err(Error::Http)?;
+
+        let status = response.status();
+        let text = response.text().await.map_err(Error::Http)?;
+
+        if status.is_success() {
+            return serde_json::from_str(&text).map_err(Error::Json);
+        }
+
+        let error: DeviceCodeErrorResponse = serde_json::from_str(&text).map_err(Error::Json)?;
+
+        Err(Error::Api {
+            status: status.as_u16(),
+            error: error.error,
+            error_message: error.error_description.unwrap_or_default(),
+        })
+    }
+
+    /// Polls for the tokens resulting from a device code authorized with
+    /// [`request_device_code`](Self::request_device_code), waiting `interval`
+    /// between attempts until the user authorizes the code (or an
+    /// unrecoverable error occurs).
+    ///
+    /// Per the OAuth 2.0 Device Authorization Grant spec, the effective
+    /// polling interval is increased by `SLOW_DOWN_INCREMENT` each time the
+    /// server responds with `slow_down`.
+    pub async fn poll_device_code_token(
+        &self,
+        device_code: &str,
+        interval: Duration,
+    ) -> Result<MicrosoftTokenResponse> {
+        let url = format!("{}/token", self.endpoints.oauth);
+        let mut interval = interval;
+
+        loop {
+            let response = self
+                .client
+                .post(&url)
+                .form(&[
+                    ("client_id", self.client_id.as_str()),
+                    ("device_code", device_code),
+                    ("grant_type", DEVICE_CODE_GRANT_TYPE),
+                ])
+                .send()
+                .await
+                .map_err(Error::Http)?;
+
+            let status = response.status();
+            let text = response.text().await.map_err(Error::Http)?;
+
+            if status.is_success() {
+                return serde_json::from_str(&text).map_err(Error::Json);
+            }
+
+            let error: DeviceCodeErrorResponse =
+                serde_json::from_str(&text).map_err(Error::Json)?;
+
+            match error.error.as_str() {
+                "authorization_pending" => {
+                    tokio::time::sleep(interval).await;
+                }
+                "slow_down" => {
+                    interval += SLOW_DOWN_INCREMENT;
+                    tokio::time::sleep(interval).await;
+                }
+                _ => {
+                    return Err(Error::Api {
+                        status: status.as_u16(),
+                        error: error.error,
+                        error_message: error.error_description.unwrap_or_default(),
+                    })
+                }
+            }
+        }
+    }
+
+    /// Exchanges a Microsoft account access token for an Xbox Live token.
+    ///
+    /// Returns `Err(Error::Api)` if Xbox Live rejects the token, e.g. with
+    /// `XErr` 2148916233 for a child account without parental consent.
+    pub async fn authenticate_xbox_live(
+        &self,
+        microsoft_access_token: &str,
+    ) -> Result<XboxLiveToken> {
+        let url = format!("{}/user/authenticate", self.endpoints.xbox_live);
+
+        let payload = json!({
+            "Properties": {
+                "AuthMethod": "RPS",
+                "SiteName": "user.auth.xboxlive.com",
+                "RpsTicket": format!("d={}", microsoft_access_token)
+            },
+            "RelyingParty": "http://auth.xboxlive.com",
+            "TokenType": "JWT"
+        });
+
+        let response = self
+            .client
+            .post(&url)
+            .body(payload.to_string())
+            .header("Content-Type", "application/json")
+            .send()
+            .await
+            .map_err(Error::Http)?;
+
+        let response: XboxServiceResponse = parse_xbox_response(response).await?;
+        XboxLiveToken::try_from(response)
+    }
+
+    /// Exchanges an Xbox Live token for an XSTS token, scoped for use with
+    /// the Minecraft services API.
+    ///
+    /// Returns `Err(Error::Api)` if XSTS rejects the token, e.g. with
+    /// `XErr` 2148916233 for a child account without parental consent, or
+    /// 2148916238 for an account with no Xbox profile.
+    pub async fn authenticate_xsts(&self, xbox_live_token: &str) -> Result<XboxLiveToken> {
+        let url = format!("{}/xsts/authorize", self.endpoints.xsts);
+
+        let payload = json!({
+            "Properties": {
+                "SandboxId": "RETAIL",
+                "UserTokens": [xbox_live_token]
+            },
+            "RelyingParty": "rp://api.minecraftservices.com/",
+            "TokenType": "JWT"
+        });
+
+        let response = self
+            .client
+            .post(&url)
+            .body(payload.to_string())
+            .header("Content-Type", "application/json")
+            .send()
+            .await
+            .map_err(Error::Http)?;
+
+        let response: XboxServiceResponse = parse_xbox_response(response).await?;
+        XboxLiveToken::try_from(response)
+    }
+
+    /// Exchanges an XSTS token for a Minecraft access token, which can be
+    /// used with [`profile`](Self::profile) and
+    /// [`client_auth`](crate::client_auth).
+    ///
+    /// Returns `Err(Error::Api)` if the Minecraft services API rejects the
+    /// token, e.g. if the account owns no copy of Minecraft.
+    pub async fn login_with_xbox(&self, xsts_token: &XboxLiveToken) -> Result<String> {
+        let url = format!(
+            "{}/authentication/login_with_xbox",
+            self.endpoints.minecraft_services
+        );
+
+        let payload = json!({
+            "identityToken": format!("XBL3.0 x={};{}", xsts_token.user_hash, xsts_token.token)
+        });
+
+        let response = self
+            .client
+            .post(&url)
+            .body(payload.to_string())
+            .header("Content-Type", "application/json")
+            .send()
+            .await
+            .map_err(Error::Http)?;
+
+        let response: MinecraftLoginResponse = parse_json_response(response)
+            .await?
+            .ok_or_else(|| Error::InvalidResponse("empty response body".to_string()))?;
+        Ok(response.access_token)
+    }
+
+    /// Fetches the Minecraft profile (UUID and username) associated with a
+    /// Minecraft access token obtained from [`login_with_xbox`](Self::login_with_xbox).
+    ///
+    /// Returns `Err(Error::Api)` if the Minecraft services API rejects the
+    /// token, or if the account has no Minecraft profile (reported as a 404
+    /// with a JSON error body).
+    pub async fn profile(&self, minecraft_access_token: &str) -> Result<MinecraftProfile> {
+        let url = format!("{}/minecraft/profile", self.endpoints.minecraft_services);
+
+        let response = self
+            .client
+            .get(&url)
+            .bearer_auth(minecraft_access_token)
+            .send()
+            .await
+            .map_err(Error::Http)?;
+
+        parse_json_response(response)
+            .await?
+            .ok_or_else(|| Error::InvalidResponse("empty response body".to_string()))
+    }
+
+    /// Drives the remainder of the authentication chain given a Microsoft
+    /// account access token: Xbox Live, then XSTS, then the Minecraft
+    /// access token and profile.
+    ///
+    /// Returns the Minecraft access token (usable with
+    /// [`client_auth`](crate::client_auth)) alongside the player's profile.
+    pub async fn finish_device_code_login(
+        &self,
+        microsoft_access_token: &str,
+    ) -> Result<(String, MinecraftProfile)> {
+        let xbl_token = self.authenticate_xbox_live(microsoft_access_token).await?;
+        let xsts_token = self.authenticate_xsts(&xbl_token.token).await?;
+        let access_token = self.login_with_xbox(&xsts_token).await?;
+        let profile = self.profile(&access_token).await?;
+
+        Ok((access_token, profile))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_request_device_code() -> Result<()> {
+        let expected_response = DeviceCodeResponse {
+            device_code: "device_code_123".to_string(),
+            user_code: "ABCDEFGH".to_string(),
+            verification_uri: "https://microsoft.com/link".to_string(),
+            expires_in: 900,
+            interval: 5,
+            message: "To sign in, use a web browser...".to_string(),
+        };
+
+        let _m = mockito::mock("POST", "/devicecode")
+            .with_body(serde_json::to_string(&expected_response).unwrap())
+            .create();
+
+        let auth = MicrosoftAuth::new("client-id");
+        let response = auth.request_device_code().await?;
+
+        assert_eq!(response, expected_response);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_request_device_code_rejected() {
+        let _m = mockito::mock("POST", "/devicecode")
+            .with_status(400)
+            .with_body(
+                r#"{"error": "invalid_client", "error_description": "the client_id is invalid"}"#,
+            )
+            .create();
+
+        let auth = MicrosoftAuth::new("client-id");
+        let result = auth.request_device_code().await;
+
+        assert_eq!(
+            result.unwrap_err(),
+            Error::Api {
+                status: 400,
+                error: "invalid_client".to_string(),
+                error_message: "the client_id is invalid".to_string(),
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_poll_device_code_token_pending_then_success() -> Result<()> {
+        let expected_response = MicrosoftTokenResponse {
+            access_token: "ms_access_token".to_string(),
+            refresh_token: "ms_refresh_token".to_string(),
+            expires_in: 3600,
+        };
+
+        let _pending = mockito::mock("POST", "/token")
+            .with_status(400)
+            .with_body(r#"{"error": "authorization_pending"}"#)
+            .expect(1)
+            .create();
+        let _success = mockito::mock("POST", "/token")
+            .with_body(serde_json::to_string(&expected_response).unwrap())
+            .create();
+
+        let auth = MicrosoftAuth::new("client-id");
+        let response = auth
+            .poll_device_code_token("device_code_123", Duration::from_millis(1))
+            .await?;
+
+        assert_eq!(response, expected_response);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_poll_device_code_token_slow_down_then_success() -> Result<()> {
+        let expected_response = MicrosoftTokenResponse {
+            access_token: "ms_access_token".to_string(),
+            refresh_token: "ms_refresh_token".to_string(),
+            expires_in: 3600,
+        };
+
+        let _slow_down = mockito::mock("POST", "/token")
+            .with_status(400)
+            .with_body(r#"{"error": "slow_down"}"#)
+            .expect(1)
+            .create();
+        let _success = mockito::mock("POST", "/token")
+            .with_body(serde_json::to_string(&expected_response).unwrap())
+            .create();
+
+        let auth = MicrosoftAuth::new("client-id");
+        let response = auth
+            .poll_device_code_token("device_code_123", Duration::from_millis(1))
+            .await?;
+
+        assert_eq!(response, expected_response);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_poll_device_code_token_terminal_error() {
+        let _m = mockito::mock("POST", "/token")
+            .with_status(400)
+            .with_body(
+                r#"{"error": "expired_token", "error_description": "the device code has expired"}"#,
+            )
+            .create();
+
+        let auth = MicrosoftAuth::new("client-id");
+        let result = auth
+            .poll_device_code_token("device_code_123", Duration::from_millis(1))
+            .await;
+
+        assert_eq!(
+            result.unwrap_err(),
+            Error::Api {
+                status: 400,
+                error: "expired_token".to_string(),
+                error_message: "the device code has expired".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_xbox_live_token_missing_xui_claim() {
+        let response = XboxServiceResponse {
+            token: "xbl_token".to_string(),
+            display_claims: DisplayClaims { xui: vec![] },
+        };
+
+        assert!(XboxLiveToken::try_from(response).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_authenticate_xbox_live_rejected() {
+        let _m = mockito::mock("POST", "/user/authenticate")
+            .with_status(401)
+            .with_body(r#"{"Identity": "0", "XErr": 2148916233, "Message": ""}"#)
+            .create();
+
+        let auth = MicrosoftAuth::new("client-id");
+        let result = auth.authenticate_xbox_live("ms_access_token").await;
+
+        assert_eq!(
+            result.unwrap_err(),
+            Error::Api {
+                status: 401,
+                error: "XErr 2148916233".to_string(),
+                error_message: "".to_string(),
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_profile_no_minecraft_profile() {
+        let _m = mockito::mock("GET", "/minecraft/profile")
+            .with_status(404)
+            .with_body(
+                r#"{"path": "/minecraft/profile", "error": "NOT_FOUND", "errorMessage": "not found"}"#,
+            )
+            .create();
+
+        let auth = MicrosoftAuth::new("client-id");
+        let result = auth.profile("mc_access_token").await;
+
+        assert_eq!(
+            result.unwrap_err(),
+            Error::Api {
+                status: 404,
+                error: "NOT_FOUND".to_string(),
+                error_message: "not found".to_string(),
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_microsoft_auth_custom_endpoints() -> Result<()> {
+        let expected_response = DeviceCodeResponse {
+            device_code: "device_code_123".to_string(),
+            user_code: "ABCDEFGH".to_string(),
+            verification_uri: "https://microsoft.com/link".to_string(),
+            expires_in: 900,
+            interval: 5,
+            message: "To sign in, use a web browser...".to_string(),
+        };
+
+        let _m = mockito::mock("POST", "/devicecode")
+            .with_body(serde_json::to_string(&expected_response).unwrap())
+            .create();
+
+        let url = mockito::server_url();
+        let auth = MicrosoftAuth::with_endpoints(
+            "client-id",
+            MicrosoftEndpoints {
+                oauth: url.clone(),
+                xbox_live: url.clone(),
+                xsts: url.clone(),
+                minecraft_services: url,
+                timeout: crate::DEFAULT_TIMEOUT,
+            },
+        );
+        let response = auth.request_device_code().await?;
+
+        assert_eq!(response, expected_response);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_finish_device_code_login() -> Result<()> {
+        let xbl_response = XboxServiceResponse {
+            token: "xbl_token".to_string(),
+            display_claims: DisplayClaims {
+                xui: vec![XuiClaim {
+                    uhs: "user_hash".to_string(),
+                }],
+            },
+        };
+        let _xbl = mockito::mock("POST", "/user/authenticate")
+            .with_body(serde_json::to_string(&xbl_response).unwrap())
+            .create();
+
+        let xsts_response = XboxServiceResponse {
+            token: "xsts_token".to_string(),
+            display_claims: DisplayClaims {
+                xui: vec![XuiClaim {
+                    uhs: "user_hash".to_string(),
+                }],
+            },
+        };
+        let _xsts = mockito::mock("POST", "/xsts/authorize")
+            .with_body(serde_json::to_string(&xsts_response).unwrap())
+            .create();
+
+        let _mc_login = mockito::mock("POST", "/authentication/login_with_xbox")
+            .with_body(r#"{"access_token": "mc_access_token"}"#)
+            .create();
+
+        let expected_profile = MinecraftProfile {
+            id: Uuid::new_v4(),
+            name: "Notch".to_string(),
+        };
+        let _profile = mockito::mock("GET", "/minecraft/profile")
+            .with_body(serde_json::to_string(&expected_profile).unwrap())
+            .create();
+
+        let auth = MicrosoftAuth::new("client-id");
+        let (access_token, profile) = auth.finish_device_code_login("ms_access_token").await?;
+
+        assert_eq!(access_token, "mc_access_token");
+        assert_eq!(profile, expected_profile);
+
+        Ok(())
+    }
+}